@@ -18,7 +18,10 @@
 
 //! A RPC handler to create sync states for light clients.
 //!
-//! Currently only usable with BABE + GRANDPA.
+//! The hardcoded, consensus-specific part of the sync state is supplied by one
+//! [`SyncStateProvider`] per consensus engine. [`BabeSyncStateProvider`] and
+//! [`GrandpaSyncStateProvider`] are the default implementations, but [`SyncStateRpc`] is generic
+//! over any provider, so e.g. Aura-based chains can plug in their own.
 //!
 //! # Usage
 //!
@@ -41,17 +44,26 @@
 
 #![deny(unused_crate_dependencies)]
 
+use futures::{FutureExt, StreamExt};
 use jsonrpsee::{
 	core::{Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
+	PendingSubscriptionSink, SubscriptionSink,
 };
-use sc_client_api::StorageData;
+use sc_client_api::{BlockchainEvents, StorageData};
+use sc_consensus_babe::{BabeConfiguration, Epoch};
+use sc_consensus_epochs::descendent_query;
+use sc_finality_grandpa::{AuthoritySetChanges, GrandpaJustification};
+use sc_network::config::MultiaddrWithPeerId;
+use sc_rpc::SubscriptionTaskExecutor;
 use sp_blockchain::HeaderBackend;
+use sp_consensus_babe::{AuthorityId as BabeAuthorityId, Randomness as BabeRandomness, Slot};
+use sp_finality_grandpa::AuthorityList;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, NumberFor},
+	traits::{Block as BlockT, NumberFor, Zero},
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 type SharedAuthoritySet<TBl> =
 	sc_finality_grandpa::SharedAuthoritySet<<TBl as BlockT>::Hash, NumberFor<TBl>>;
@@ -73,6 +85,18 @@ pub enum Error<Block: BlockT> {
 		Read the `sc-sync-state-rpc` crate docs on how to do this!"
 	)]
 	LightSyncStateExtensionNotFound,
+
+	#[error("No justification found for finalized block {0:?}")]
+	MissingJustification(Block::Hash),
+
+	#[error("`since` block {0:?} is ahead of the current finalized number")]
+	SinceBlockNotFinalized(NumberFor<Block>),
+
+	#[error("Finalized block {0:?} does not contain a BABE pre-digest")]
+	MissingBabePreDigest(Block::Hash),
+
+	#[error("No viable BABE epoch found for the finalized head")]
+	MissingEpochData,
 }
 
 /// Serialize the given `val` by encoding it with SCALE codec and serializing it as hex.
@@ -90,92 +114,580 @@ fn serialize_encoded<S: serde::Serializer, T: codec::Encode>(
 /// chain-spec as an extension.
 pub type LightSyncStateExtension = Option<serde_json::Value>;
 
+/// A source of the hardcoded, consensus-specific portion of a [`LightSyncState`].
+///
+/// Implemented once per consensus engine so [`SyncStateRpc`] is not hardwired to BABE +
+/// GRANDPA; see [`BabeSyncStateProvider`] and [`GrandpaSyncStateProvider`] for the defaults.
+pub trait SyncStateProvider<Block: BlockT> {
+	/// The serializable payload this provider contributes to the sync state.
+	type Serialized: serde::Serialize + Clone;
+
+	/// Build this provider's contribution to the sync state.
+	///
+	/// `extended` requests any additional, more expensive data the provider can supply on top
+	/// of the bare minimum needed to sync quickly (e.g. GRANDPA's authority-set-change proof
+	/// chain); providers that have nothing extra to offer may ignore it.
+	fn get_sync_state(&self, extended: bool) -> Result<Self::Serialized, Error<Block>>;
+}
+
+/// A source of addresses a light client could dial to actually reach the chain: the node's own
+/// public multiaddr(s), a snapshot of its currently connected peers, or both.
+///
+/// Typically backed by a handle to the running network service; implemented separately from
+/// [`SyncStateRpc`] itself so this crate does not need to depend on `sc-network` beyond the
+/// [`MultiaddrWithPeerId`] type.
+pub trait BootNodesProvider: Send + Sync {
+	/// Addresses to add to a generated sync spec's `bootNodes`, on top of whatever the static
+	/// chain spec already lists.
+	fn boot_nodes(&self) -> Vec<MultiaddrWithPeerId>;
+}
+
+/// A [`SyncStateProvider`] that can also prove finality has advanced past a given block, e.g.
+/// via a GRANDPA-style justification.
+pub trait FinalityProofProvider<Block: BlockT>: SyncStateProvider<Block> {
+	/// Build a compact proof that finality has advanced past `since`.
+	fn finality_update(
+		&self,
+		since: NumberFor<Block>,
+	) -> Result<LightClientFinalityUpdate<Block>, Error<Block>>;
+}
+
+/// A [`SyncStateProvider`] that can also report the current best, possibly still unfinalized,
+/// header and its engine-specific weight.
+pub trait OptimisticUpdateProvider<Block: BlockT>: SyncStateProvider<Block> {
+	/// Build a pointer at the current best header and its weight.
+	fn optimistic_update(&self) -> Result<LightClientOptimisticUpdate<Block>, Error<Block>>;
+}
+
+/// A [`SyncStateProvider`] that can also report its block-authorship schedule, e.g. a BABE
+/// epoch's authorities, randomness, and slot range.
+pub trait EpochAuthorshipProvider<Block: BlockT>: SyncStateProvider<Block> {
+	/// Build the authorship descriptor for the current authorship period and the one immediately
+	/// following it.
+	fn epoch_authorship(&self) -> Result<EpochAuthorshipUpdate, Error<Block>>;
+}
+
 /// Hardcoded information that allows light clients to sync quickly.
-#[derive(serde::Serialize, Clone)]
+#[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
-pub struct LightSyncState<Block: BlockT> {
+pub struct LightSyncState<Block, Babe, Grandpa>
+where
+	Block: BlockT,
+	Babe: SyncStateProvider<Block>,
+	Grandpa: SyncStateProvider<Block>,
+{
 	/// The header of the best finalized block.
 	#[serde(serialize_with = "serialize_encoded")]
 	pub finalized_block_header: <Block as BlockT>::Header,
+	/// The BABE (or other block-authorship engine) contribution to the sync state.
+	#[serde(flatten)]
+	pub babe: Babe::Serialized,
+	/// The GRANDPA contribution to the sync state.
+	#[serde(flatten)]
+	pub grandpa: Grandpa::Serialized,
+}
+
+/// The [`SyncStateProvider`] for BABE: the epoch changes tree and the finalized block's weight.
+pub struct BabeSyncStateProvider<Block: BlockT, Client> {
+	client: Arc<Client>,
+	shared_epoch_changes: SharedEpochChanges<Block>,
+	babe_config: BabeConfiguration,
+}
+
+impl<Block: BlockT, Client> BabeSyncStateProvider<Block, Client> {
+	/// Create a new BABE sync state provider.
+	pub fn new(
+		client: Arc<Client>,
+		shared_epoch_changes: SharedEpochChanges<Block>,
+		babe_config: BabeConfiguration,
+	) -> Self {
+		Self { client, shared_epoch_changes, babe_config }
+	}
+}
+
+/// The serialized contribution of [`BabeSyncStateProvider`] to a [`LightSyncState`].
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BabeSyncState<Block: BlockT> {
 	/// The epoch changes tree for babe.
 	#[serde(serialize_with = "serialize_encoded")]
 	pub babe_epoch_changes: sc_consensus_epochs::EpochChangesFor<Block, sc_consensus_babe::Epoch>,
 	/// The babe weight of the finalized block.
 	pub babe_finalized_block_weight: sc_consensus_babe::BabeBlockWeight,
+}
+
+impl<Block, Client> SyncStateProvider<Block> for BabeSyncStateProvider<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + sc_client_api::AuxStore,
+{
+	type Serialized = BabeSyncState<Block>;
+
+	fn get_sync_state(&self, _extended: bool) -> Result<Self::Serialized, Error<Block>> {
+		let finalized_hash = self.client.info().finalized_hash;
+		let babe_finalized_block_weight =
+			sc_consensus_babe::aux_schema::load_block_weight(&*self.client, finalized_hash)?
+				.ok_or_else(|| Error::LoadingBlockWeightFailed(finalized_hash))?;
+
+		Ok(BabeSyncState {
+			babe_epoch_changes: self.shared_epoch_changes.shared_data().clone(),
+			babe_finalized_block_weight,
+		})
+	}
+}
+
+impl<Block, Client> OptimisticUpdateProvider<Block> for BabeSyncStateProvider<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + sc_client_api::AuxStore,
+{
+	fn optimistic_update(&self) -> Result<LightClientOptimisticUpdate<Block>, Error<Block>> {
+		let best_hash = self.client.info().best_hash;
+		let best_header = header_of(&*self.client, best_hash)?;
+		let best_block_weight =
+			sc_consensus_babe::aux_schema::load_block_weight(&*self.client, best_hash)?
+				.ok_or_else(|| Error::LoadingBlockWeightFailed(best_hash))?;
+
+		Ok(LightClientOptimisticUpdate { best_header, best_block_weight })
+	}
+}
+
+impl<Block, Client> EpochAuthorshipProvider<Block> for BabeSyncStateProvider<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + sc_client_api::AuxStore,
+{
+	fn epoch_authorship(&self) -> Result<EpochAuthorshipUpdate, Error<Block>> {
+		let finalized_hash = self.client.info().finalized_hash;
+		let finalized_number = self.client.info().finalized_number;
+		let finalized_header = header_of(&*self.client, finalized_hash)?;
+		let slot = sc_consensus_babe::find_pre_digest::<Block>(&finalized_header)
+			.map_err(|_| Error::MissingBabePreDigest(finalized_hash))?
+			.slot();
+
+		let epoch_changes = self.shared_epoch_changes.shared_data();
+		let current_epoch = epoch_changes
+			.epoch_data_for_child_of(
+				descendent_query(&*self.client),
+				&finalized_hash,
+				finalized_number,
+				slot,
+				|slot| Epoch::genesis(&self.babe_config, slot),
+			)
+			.map_err(|_| Error::MissingEpochData)?
+			.ok_or(Error::MissingEpochData)?;
+		let next_slot = Slot::from(*current_epoch.start_slot + current_epoch.duration);
+		let next_epoch = epoch_changes
+			.epoch_data_for_child_of(
+				descendent_query(&*self.client),
+				&finalized_hash,
+				finalized_number,
+				next_slot,
+				|slot| Epoch::genesis(&self.babe_config, slot),
+			)
+			.map_err(|_| Error::MissingEpochData)?
+			.ok_or(Error::MissingEpochData)?;
+
+		Ok(EpochAuthorshipUpdate {
+			current: epoch_authorship_from(&current_epoch),
+			next: epoch_authorship_from(&next_epoch),
+		})
+	}
+}
+
+/// The [`SyncStateProvider`] for GRANDPA: the current authority set, and optionally a full
+/// proof chain of every authority set change since genesis (or since `proof_since`, if the
+/// provider was built with a checkpoint).
+pub struct GrandpaSyncStateProvider<Block: BlockT, Client> {
+	client: Arc<Client>,
+	shared_authority_set: SharedAuthoritySet<Block>,
+	proof_since: NumberFor<Block>,
+}
+
+impl<Block: BlockT, Client> GrandpaSyncStateProvider<Block, Client> {
+	/// Create a new GRANDPA sync state provider whose authority-set proof chain covers the full
+	/// history, from genesis.
+	pub fn new(client: Arc<Client>, shared_authority_set: SharedAuthoritySet<Block>) -> Self {
+		Self::new_with_proof_since(client, shared_authority_set, Zero::zero())
+	}
+
+	/// Create a new GRANDPA sync state provider whose authority-set proof chain only covers
+	/// changes enacted after `proof_since`, e.g. because the light client already trusts the
+	/// authority set as of some later checkpoint and does not need the full history from genesis.
+	pub fn new_with_proof_since(
+		client: Arc<Client>,
+		shared_authority_set: SharedAuthoritySet<Block>,
+		proof_since: NumberFor<Block>,
+	) -> Self {
+		Self { client, shared_authority_set, proof_since }
+	}
+}
+
+/// The serialized contribution of [`GrandpaSyncStateProvider`] to a [`LightSyncState`].
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GrandpaSyncState<Block: BlockT> {
 	/// The authority set for grandpa.
 	#[serde(serialize_with = "serialize_encoded")]
 	pub grandpa_authority_set:
 		sc_finality_grandpa::AuthoritySet<<Block as BlockT>::Hash, NumberFor<Block>>,
+	/// A cryptographic proof of every GRANDPA authority set change from genesis (or the last
+	/// checkpoint) to the finalized block, letting a light client derive
+	/// `grandpa_authority_set` itself instead of trusting it blindly. Only populated when
+	/// requested, since walking and re-fetching the whole change history is comparatively
+	/// expensive.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub authority_set_proof: Option<Vec<AuthoritySetChangeProof<Block>>>,
+}
+
+impl<Block, Client> SyncStateProvider<Block> for GrandpaSyncStateProvider<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + sp_blockchain::Backend<Block>,
+{
+	type Serialized = GrandpaSyncState<Block>;
+
+	fn get_sync_state(&self, extended: bool) -> Result<Self::Serialized, Error<Block>> {
+		let authority_set_proof = if extended {
+			Some(authority_set_proof_chain(
+				&*self.client,
+				&self.shared_authority_set,
+				self.proof_since,
+			)?)
+		} else {
+			None
+		};
+
+		Ok(GrandpaSyncState {
+			grandpa_authority_set: self.shared_authority_set.clone_inner(),
+			authority_set_proof,
+		})
+	}
+}
+
+impl<Block: BlockT, Client> GrandpaSyncStateProvider<Block, Client>
+where
+	Client: HeaderBackend<Block>,
+{
+	/// Collect every GRANDPA authority set transition enacted after `since`; a transition
+	/// enacted at `since` itself is not included.
+	fn authority_set_changes_since(
+		&self,
+		since: NumberFor<Block>,
+	) -> Result<Vec<AuthoritySetChange<Block>>, Error<Block>> {
+		let changes: AuthoritySetChanges<NumberFor<Block>> =
+			self.shared_authority_set.authority_set_changes();
+
+		changes
+			.iter_from(since)
+			.map(|(set_id, canon_height)| {
+				let canon_hash = self
+					.client
+					.hash(canon_height)?
+					.ok_or_else(|| sp_blockchain::Error::MissingHeader(canon_height.to_string()))?;
+				let header = header_of(&*self.client, canon_hash)?;
+
+				Ok(AuthoritySetChange {
+					set_id,
+					canon_height,
+					authorities: authority_list_enacted_in::<Block>(&header),
+				})
+			})
+			.collect()
+	}
+}
+
+impl<Block, Client> FinalityProofProvider<Block> for GrandpaSyncStateProvider<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + sp_blockchain::Backend<Block>,
+{
+	fn finality_update(
+		&self,
+		since: NumberFor<Block>,
+	) -> Result<LightClientFinalityUpdate<Block>, Error<Block>> {
+		let finalized_hash = self.client.info().finalized_hash;
+		let finalized_number = self.client.info().finalized_number;
+		check_since_is_finalized::<Block>(since, finalized_number)?;
+
+		let finalized_header = header_of(&*self.client, finalized_hash)?;
+		let justification = grandpa_justification_for(&*self.client, finalized_hash)?;
+		let authority_set_changes = self.authority_set_changes_since(since)?;
+
+		Ok(LightClientFinalityUpdate { finalized_header, justification, authority_set_changes })
+	}
+}
+
+/// The header at which a GRANDPA authority set change was enacted, the justification proving
+/// its finality, and the authority list that takes effect from it onwards.
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoritySetChangeProof<Block: BlockT> {
+	/// The header at which the authority set change was enacted.
+	#[serde(serialize_with = "serialize_encoded")]
+	pub header: Block::Header,
+	/// The justification finalizing `header`.
+	#[serde(serialize_with = "serialize_encoded")]
+	pub justification: GrandpaJustification<Block>,
+	/// The authority list that takes effect from `header` onwards.
+	pub authorities: AuthorityList,
+}
+
+/// A single GRANDPA authority set transition, scheduled or applied at `canon_height`.
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthoritySetChange<Block: BlockT> {
+	/// The id of the authority set whose last block is `canon_height`; `authorities` make up
+	/// the set that follows it, with id `set_id + 1`.
+	pub set_id: u64,
+	/// The height at which the change was enacted.
+	pub canon_height: NumberFor<Block>,
+	/// The new authority list, in effect from `canon_height` onwards.
+	pub authorities: AuthorityList,
+}
+
+/// A compact proof that the chain has finalized a new header, without shipping the whole
+/// [`LightSyncState`].
+///
+/// Verifiable against an already-trusted `grandpa_authority_set`: the justification proves
+/// `finalized_header` final under the authority set named by `authority_set_changes`' last
+/// entry (or the caller's already-trusted set, if `authority_set_changes` is empty).
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LightClientFinalityUpdate<Block: BlockT> {
+	/// The new finalized header.
+	pub finalized_header: Block::Header,
+	/// The GRANDPA justification proving `finalized_header`'s finality.
+	#[serde(serialize_with = "serialize_encoded")]
+	pub justification: GrandpaJustification<Block>,
+	/// Authority set transitions enacted since the caller's `since` block, oldest first.
+	pub authority_set_changes: Vec<AuthoritySetChange<Block>>,
+}
+
+/// A compact pointer at the current best, possibly still unfinalized, header.
+///
+/// Cheaper than [`LightClientFinalityUpdate`] but gives weaker guarantees: the header is only
+/// as trustworthy as the BABE block weight backing it, not a GRANDPA justification.
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LightClientOptimisticUpdate<Block: BlockT> {
+	/// The current best header.
+	pub best_header: Block::Header,
+	/// The BABE block weight of `best_header`.
+	pub best_block_weight: sc_consensus_babe::BabeBlockWeight,
+}
+
+/// A BABE epoch's authorship parameters: who may author within it, with what VRF randomness,
+/// and over how many slots.
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochAuthorship {
+	/// The epoch's authorities, alongside each one's block-production weight.
+	pub authorities: Vec<(BabeAuthorityId, sc_consensus_babe::BabeAuthorityWeight)>,
+	/// The VRF randomness seed slot assignments for this epoch are derived from.
+	pub randomness: BabeRandomness,
+	/// The first slot of the epoch.
+	pub start_slot: Slot,
+	/// The number of slots that make up the epoch.
+	pub slot_duration: u64,
+}
+
+/// The authorship parameters for the BABE epoch containing the finalized head, and for the
+/// epoch immediately following it.
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochAuthorshipUpdate {
+	/// The epoch containing the finalized head.
+	pub current: EpochAuthorship,
+	/// The epoch immediately following `current`.
+	pub next: EpochAuthorship,
 }
 
 /// An api for sync state RPC calls.
 #[rpc(client, server)]
-pub trait SyncStateRpcApi {
+pub trait SyncStateRpcApi<Block: BlockT> {
 	/// Returns the JSON serialized chainspec running the node, with a sync state.
+	///
+	/// If `with_authority_set_proof` is set, the embedded `grandpa_authority_set` is
+	/// accompanied by a full proof chain of every authority set change since genesis, so a
+	/// light client can derive the authority set itself rather than trusting it blindly.
+	///
+	/// If `with_live_boot_nodes` is set, the spec's `bootNodes` are augmented with addresses
+	/// from this node's [`BootNodesProvider`] (if one was configured), so the result is
+	/// immediately dialable rather than only as good as whatever the static chain spec lists.
 	#[method(name = "sync_state_genSyncSpec")]
-	fn system_gen_sync_spec(&self, raw: bool) -> RpcResult<String>;
+	fn system_gen_sync_spec(
+		&self,
+		raw: bool,
+		with_authority_set_proof: bool,
+		with_live_boot_nodes: bool,
+	) -> RpcResult<String>;
+
+	/// Returns a compact proof that finality has advanced past `since`, carrying only the new
+	/// finalized header, its justification, and any GRANDPA authority set changes enacted in
+	/// between. Cheaper than re-requesting the whole sync spec once a light client is synced.
+	#[method(name = "sync_state_finalityUpdate")]
+	fn sync_state_finality_update(
+		&self,
+		since: NumberFor<Block>,
+	) -> RpcResult<LightClientFinalityUpdate<Block>>;
+
+	/// Returns the current best (possibly unfinalized) header and its BABE block weight, so a
+	/// light client can optimistically track the head of the chain between finality updates.
+	#[method(name = "sync_state_optimisticUpdate")]
+	fn sync_state_optimistic_update(&self) -> RpcResult<LightClientOptimisticUpdate<Block>>;
+
+	/// Returns the authorship parameters — authority list, VRF randomness, and slot range — of
+	/// the BABE epoch containing the finalized head, and of the epoch immediately following it.
+	#[method(name = "sync_state_epochAuthorship")]
+	fn sync_state_epoch_authorship(&self) -> RpcResult<EpochAuthorshipUpdate>;
+
+	/// Subscribes to the sync state, sending the current [`LightSyncState`] right away and again
+	/// every time a block is finalized. Each message is a string holding the state's JSON
+	/// serialization, the same form as [`SyncStateRpcApiServer::system_gen_sync_spec`]'s
+	/// extension payload: the `serialize_encoded` fields are SCALE-encoded as hex, the state as a
+	/// whole is not. BABE epoch data is re-read at each finalization; epoch changes are not
+	/// watched separately.
+	#[subscription(
+		name = "sync_state_subscribeSyncState" => "sync_state_syncState",
+		unsubscribe = "sync_state_unsubscribeSyncState",
+		item = String,
+	)]
+	fn subscribe_sync_state(&self);
 }
 
 /// An api for sync state RPC calls.
-pub struct SyncStateRpc<Block: BlockT, Client> {
+pub struct SyncStateRpc<
+	Block: BlockT,
+	Client,
+	Babe = BabeSyncStateProvider<Block, Client>,
+	Grandpa = GrandpaSyncStateProvider<Block, Client>,
+> {
 	chain_spec: Box<dyn sc_chain_spec::ChainSpec>,
 	client: Arc<Client>,
-	shared_authority_set: SharedAuthoritySet<Block>,
-	shared_epoch_changes: SharedEpochChanges<Block>,
+	babe_provider: Babe,
+	grandpa_provider: Grandpa,
+	boot_nodes_provider: Option<Box<dyn BootNodesProvider>>,
+	deny_unsafe: sc_rpc::DenyUnsafe,
+	executor: SubscriptionTaskExecutor,
 }
 
 impl<Block, Client> SyncStateRpc<Block, Client>
 where
 	Block: BlockT,
-	Client: HeaderBackend<Block> + sc_client_api::AuxStore + 'static,
+	Client: HeaderBackend<Block>
+		+ sp_blockchain::Backend<Block>
+		+ sc_client_api::AuxStore
+		+ BlockchainEvents<Block>
+		+ 'static,
 {
-	/// Create a new sync state RPC helper.
+	/// Create a new sync state RPC helper using the default BABE and GRANDPA providers.
+	///
+	/// Use [`Self::with_providers`] to plug in providers for a different consensus engine.
 	pub fn new(
 		chain_spec: Box<dyn sc_chain_spec::ChainSpec>,
 		client: Arc<Client>,
 		shared_authority_set: SharedAuthoritySet<Block>,
 		shared_epoch_changes: SharedEpochChanges<Block>,
+		babe_config: BabeConfiguration,
+		boot_nodes_provider: Option<Box<dyn BootNodesProvider>>,
+		deny_unsafe: sc_rpc::DenyUnsafe,
+		executor: SubscriptionTaskExecutor,
+	) -> Result<Self, Error<Block>> {
+		let babe_provider =
+			BabeSyncStateProvider::new(client.clone(), shared_epoch_changes, babe_config);
+		let grandpa_provider = GrandpaSyncStateProvider::new(client.clone(), shared_authority_set);
+
+		Self::with_providers(
+			chain_spec,
+			client,
+			babe_provider,
+			grandpa_provider,
+			boot_nodes_provider,
+			deny_unsafe,
+			executor,
+		)
+	}
+}
+
+impl<Block, Client, Babe, Grandpa> SyncStateRpc<Block, Client, Babe, Grandpa>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>
+		+ sp_blockchain::Backend<Block>
+		+ sc_client_api::AuxStore
+		+ BlockchainEvents<Block>
+		+ 'static,
+	Babe: SyncStateProvider<Block>,
+	Grandpa: SyncStateProvider<Block>,
+{
+	/// Create a new sync state RPC helper, generic over the [`SyncStateProvider`]s supplying the
+	/// hardcoded, consensus-specific part of the sync state (e.g. to support Aura instead of
+	/// BABE).
+	pub fn with_providers(
+		chain_spec: Box<dyn sc_chain_spec::ChainSpec>,
+		client: Arc<Client>,
+		babe_provider: Babe,
+		grandpa_provider: Grandpa,
+		boot_nodes_provider: Option<Box<dyn BootNodesProvider>>,
+		deny_unsafe: sc_rpc::DenyUnsafe,
+		executor: SubscriptionTaskExecutor,
 	) -> Result<Self, Error<Block>> {
 		if sc_chain_spec::get_extension::<LightSyncStateExtension>(chain_spec.extensions())
 			.is_some()
 		{
-			Ok(Self { chain_spec, client, shared_authority_set, shared_epoch_changes })
+			Ok(Self {
+				chain_spec,
+				client,
+				babe_provider,
+				grandpa_provider,
+				boot_nodes_provider,
+				deny_unsafe,
+				executor,
+			})
 		} else {
 			Err(Error::<Block>::LightSyncStateExtensionNotFound)
 		}
 	}
 
-	fn build_sync_state(&self) -> Result<LightSyncState<Block>, Error<Block>> {
-		let finalized_hash = self.client.info().finalized_hash;
-		let finalized_header = self
-			.client
-			.header(BlockId::Hash(finalized_hash))?
-			.ok_or_else(|| sp_blockchain::Error::MissingHeader(finalized_hash.to_string()))?;
-
-		let finalized_block_weight =
-			sc_consensus_babe::aux_schema::load_block_weight(&*self.client, finalized_hash)?
-				.ok_or_else(|| Error::LoadingBlockWeightFailed(finalized_hash))?;
-
-		Ok(LightSyncState {
-			finalized_block_header: finalized_header,
-			babe_epoch_changes: self.shared_epoch_changes.shared_data().clone(),
-			babe_finalized_block_weight: finalized_block_weight,
-			grandpa_authority_set: self.shared_authority_set.clone_inner(),
-		})
+	fn build_sync_state(
+		&self,
+		extended: bool,
+	) -> Result<LightSyncState<Block, Babe, Grandpa>, Error<Block>> {
+		build_sync_state_for(&*self.client, &self.babe_provider, &self.grandpa_provider, extended)
 	}
 }
 
-impl<Block, Backend> SyncStateRpcApiServer for SyncStateRpc<Block, Backend>
+impl<Block, Backend, Babe, Grandpa> SyncStateRpcApiServer<Block>
+	for SyncStateRpc<Block, Backend, Babe, Grandpa>
 where
 	Block: BlockT,
-	Backend: HeaderBackend<Block> + sc_client_api::AuxStore + 'static,
+	Backend: HeaderBackend<Block>
+		+ sp_blockchain::Backend<Block>
+		+ sc_client_api::AuxStore
+		+ BlockchainEvents<Block>
+		+ 'static,
+	Babe: SyncStateProvider<Block>
+		+ OptimisticUpdateProvider<Block>
+		+ EpochAuthorshipProvider<Block>
+		+ Clone
+		+ Send
+		+ Sync
+		+ 'static,
+	Grandpa: SyncStateProvider<Block> + FinalityProofProvider<Block> + Clone + Send + Sync + 'static,
 {
-	fn system_gen_sync_spec(&self, raw: bool) -> RpcResult<String> {
+	fn system_gen_sync_spec(
+		&self,
+		raw: bool,
+		with_authority_set_proof: bool,
+		with_live_boot_nodes: bool,
+	) -> RpcResult<String> {
 		self.deny_unsafe.check_if_safe()?;
 
-		let current_sync_state =
-			self.build_sync_state().map_err(|e| JsonRpseeError::to_call_error(e))?;
+		let current_sync_state = self
+			.build_sync_state(with_authority_set_proof)
+			.map_err(|e| JsonRpseeError::to_call_error(e))?;
 		let mut chain_spec = self.chain_spec.cloned_box();
 
 		let extension = sc_chain_spec::get_extension_mut::<LightSyncStateExtension>(
@@ -191,6 +703,391 @@ where
 			.map_err(|e| JsonRpseeError::to_call_error(e))?;
 		*extension = Some(val);
 
-		chain_spec.as_json(raw).map_err(|e| anyhow::anyhow!(e).into())
+		let json = chain_spec.as_json(raw).map_err(|e| anyhow::anyhow!(e))?;
+
+		if with_live_boot_nodes {
+			if let Some(boot_nodes_provider) = &self.boot_nodes_provider {
+				return inject_boot_nodes(&json, boot_nodes_provider.boot_nodes())
+					.map_err(|e| JsonRpseeError::to_call_error(e))
+			}
+		}
+
+		Ok(json)
+	}
+
+	fn sync_state_finality_update(
+		&self,
+		since: NumberFor<Block>,
+	) -> RpcResult<LightClientFinalityUpdate<Block>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		self.grandpa_provider.finality_update(since).map_err(JsonRpseeError::to_call_error)
+	}
+
+	fn sync_state_optimistic_update(&self) -> RpcResult<LightClientOptimisticUpdate<Block>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		self.babe_provider.optimistic_update().map_err(JsonRpseeError::to_call_error)
+	}
+
+	fn sync_state_epoch_authorship(&self) -> RpcResult<EpochAuthorshipUpdate> {
+		self.deny_unsafe.check_if_safe()?;
+
+		self.babe_provider.epoch_authorship().map_err(JsonRpseeError::to_call_error)
+	}
+
+	fn subscribe_sync_state(&self, pending: PendingSubscriptionSink) {
+		let client = self.client.clone();
+		let babe_provider = self.babe_provider.clone();
+		let grandpa_provider = self.grandpa_provider.clone();
+		let deny_unsafe = self.deny_unsafe.clone();
+
+		// Every time GRANDPA finalizes a block the BABE epoch tree may also have advanced, so
+		// re-deriving the full sync state off the finality notification stream keeps both in
+		// lock-step without needing a second, epoch-specific notification channel.
+		let notifications = {
+			let client = client.clone();
+			let babe_provider = babe_provider.clone();
+			let grandpa_provider = grandpa_provider.clone();
+			client.finality_notification_stream().filter_map(move |_notification| {
+				let item = build_sync_state_for(&*client, &babe_provider, &grandpa_provider, false)
+					.ok()
+					.map(|state| serialize_sync_state_hex(&state));
+				async move { item }
+			})
+		};
+
+		let fut = async move {
+			if let Err(err) = deny_unsafe.check_if_safe() {
+				pending.reject(JsonRpseeError::from(err)).await;
+				return
+			}
+
+			let sink = match pending.accept().await {
+				Ok(sink) => sink,
+				Err(_) => return,
+			};
+
+			// A client subscribing is typically already bootstrapped and waiting to stay current;
+			// push it the present sync state right away instead of making it wait for the next
+			// finality notification, which may be a long time off.
+			let current = build_sync_state_for(&*client, &babe_provider, &grandpa_provider, false)
+				.ok()
+				.map(|state| serialize_sync_state_hex(&state));
+			let stream = futures::stream::iter(current).chain(notifications);
+
+			pipe_sync_state_stream(sink, stream).await;
+		};
+
+		self.executor.spawn("sync-state-subscription", None, fut.boxed());
+	}
+}
+
+/// Check that `since` does not name a block past the current finalized tip.
+fn check_since_is_finalized<Block: BlockT>(
+	since: NumberFor<Block>,
+	finalized_number: NumberFor<Block>,
+) -> Result<(), Error<Block>> {
+	if since > finalized_number {
+		Err(Error::SinceBlockNotFinalized(since))
+	} else {
+		Ok(())
+	}
+}
+
+/// Fetch the header for `hash`, turning a missing header into an [`Error::Blockchain`].
+fn header_of<Block, Client>(
+	client: &Client,
+	hash: Block::Hash,
+) -> Result<Block::Header, Error<Block>>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>,
+{
+	client
+		.header(BlockId::Hash(hash))?
+		.ok_or_else(|| sp_blockchain::Error::MissingHeader(hash.to_string()).into())
+}
+
+/// Build a [`LightSyncState`] snapshot from the individual pieces held by [`SyncStateRpc`].
+///
+/// Factored out of [`SyncStateRpc::build_sync_state`] so the subscription task, which only
+/// holds cloned handles rather than a whole `SyncStateRpc`, can rebuild the state on every
+/// finality notification.
+fn build_sync_state_for<Block, Client, Babe, Grandpa>(
+	client: &Client,
+	babe_provider: &Babe,
+	grandpa_provider: &Grandpa,
+	extended: bool,
+) -> Result<LightSyncState<Block, Babe, Grandpa>, Error<Block>>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>,
+	Babe: SyncStateProvider<Block>,
+	Grandpa: SyncStateProvider<Block>,
+{
+	let finalized_hash = client.info().finalized_hash;
+	let finalized_header = header_of(client, finalized_hash)?;
+
+	Ok(LightSyncState {
+		finalized_block_header: finalized_header,
+		babe: babe_provider.get_sync_state(extended)?,
+		grandpa: grandpa_provider.get_sync_state(extended)?,
+	})
+}
+
+/// Fetch and decode the GRANDPA justification stored with block `hash`, failing with
+/// [`Error::MissingJustification`] if there is none or it does not decode.
+fn grandpa_justification_for<Block, Client>(
+	client: &Client,
+	hash: Block::Hash,
+) -> Result<GrandpaJustification<Block>, Error<Block>>
+where
+	Block: BlockT,
+	Client: sp_blockchain::Backend<Block>,
+{
+	let encoded = client
+		.justifications(BlockId::Hash(hash))?
+		.and_then(|justifications| {
+			justifications.into_justification(sp_finality_grandpa::GRANDPA_ENGINE_ID)
+		})
+		.ok_or_else(|| Error::MissingJustification(hash))?;
+
+	codec::Decode::decode(&mut &encoded[..]).map_err(|_| Error::MissingJustification(hash))
+}
+
+/// Extract the `AuthorityList` a scheduled or forced GRANDPA change digest in `header` brings
+/// into effect, if any.
+fn authority_list_enacted_in<Block: BlockT>(header: &Block::Header) -> AuthorityList {
+	sc_finality_grandpa::find_scheduled_change(header)
+		.map(|change| change.next_authorities)
+		.or_else(|| {
+			sc_finality_grandpa::find_forced_change(header).map(|(_, change)| change.next_authorities)
+		})
+		.unwrap_or_default()
+}
+
+/// Walk the GRANDPA authority set change history from `since` (genesis, if `since` is zero) to
+/// the current set, assembling a proof chain of `(header, justification, authorities)` for each
+/// transition.
+fn authority_set_proof_chain<Block, Client>(
+	client: &Client,
+	shared_authority_set: &SharedAuthoritySet<Block>,
+	since: NumberFor<Block>,
+) -> Result<Vec<AuthoritySetChangeProof<Block>>, Error<Block>>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + sp_blockchain::Backend<Block>,
+{
+	let changes: AuthoritySetChanges<NumberFor<Block>> =
+		shared_authority_set.authority_set_changes();
+
+	changes
+		.iter_from(since)
+		.map(|(_set_id, canon_height)| {
+			let canon_hash = client
+				.hash(canon_height)?
+				.ok_or_else(|| sp_blockchain::Error::MissingHeader(canon_height.to_string()))?;
+			let header = header_of(client, canon_hash)?;
+			let authorities = authority_list_enacted_in::<Block>(&header);
+			let justification = grandpa_justification_for(client, canon_hash)?;
+
+			Ok(AuthoritySetChangeProof { header, justification, authorities })
+		})
+		.collect()
+}
+
+/// Convert a BABE [`Epoch`] into its externally-facing authorship descriptor.
+fn epoch_authorship_from(epoch: &Epoch) -> EpochAuthorship {
+	EpochAuthorship {
+		authorities: epoch.authorities.clone(),
+		randomness: epoch.randomness,
+		start_slot: epoch.start_slot,
+		slot_duration: epoch.duration,
+	}
+}
+
+/// Augment a chain spec's JSON with `extra` boot node addresses, de-duplicating against
+/// whatever `bootNodes` already lists.
+///
+/// Works on the already-serialized spec rather than `ChainSpec` itself, since the trait has no
+/// way to mutate `bootNodes` after construction.
+fn inject_boot_nodes(json: &str, extra: Vec<MultiaddrWithPeerId>) -> serde_json::Result<String> {
+	let mut spec: serde_json::Value = serde_json::from_str(json)?;
+
+	if let Some(boot_nodes) =
+		spec.get_mut("bootNodes").and_then(serde_json::Value::as_array_mut)
+	{
+		let mut seen: HashSet<String> =
+			boot_nodes.iter().filter_map(|addr| addr.as_str().map(ToOwned::to_owned)).collect();
+
+		for addr in extra {
+			let addr = addr.to_string();
+			if seen.insert(addr.clone()) {
+				boot_nodes.push(serde_json::Value::String(addr));
+			}
+		}
+	}
+
+	serde_json::to_string(&spec)
+}
+
+/// Serialize a [`LightSyncState`] to JSON, with the fields marked for `serialize_encoded`
+/// SCALE-encoded and hex-wrapped (the same representation used for the chain-spec extension).
+fn serialize_sync_state_hex<Block, Babe, Grandpa>(
+	state: &LightSyncState<Block, Babe, Grandpa>,
+) -> String
+where
+	Block: BlockT,
+	Babe: SyncStateProvider<Block>,
+	Grandpa: SyncStateProvider<Block>,
+{
+	serde_json::to_string(state).unwrap_or_default()
+}
+
+/// Forward every item of `stream` into `sink` until either side closes.
+async fn pipe_sync_state_stream<S>(mut sink: SubscriptionSink, mut stream: S)
+where
+	S: futures::Stream<Item = String> + Unpin,
+{
+	loop {
+		futures::select! {
+			_ = sink.closed().fuse() => break,
+			maybe_item = stream.next().fuse() => {
+				let item = match maybe_item {
+					Some(item) => item,
+					None => break,
+				};
+				if let Ok(message) = jsonrpsee::SubscriptionMessage::from_json(&item) {
+					if sink.send(message).await.is_err() {
+						break
+					}
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A minimal concrete [`BlockT`] for exercising this crate's pure helpers.
+	type Block = sp_runtime::testing::Block<sp_runtime::testing::ExtrinsicWrapper<()>>;
+
+	fn boot_node(addr: &str) -> MultiaddrWithPeerId {
+		addr.parse().unwrap()
+	}
+
+	#[test]
+	fn check_since_is_finalized_accepts_since_at_or_before_finalized() {
+		assert!(check_since_is_finalized::<Block>(5, 10).is_ok());
+		assert!(check_since_is_finalized::<Block>(10, 10).is_ok());
+	}
+
+	#[test]
+	fn check_since_is_finalized_rejects_since_ahead_of_finalized() {
+		let err = check_since_is_finalized::<Block>(11, 10).unwrap_err();
+		assert!(matches!(err, Error::SinceBlockNotFinalized(11)));
+	}
+
+	fn header_with_digest(digest: sp_runtime::Digest) -> sp_runtime::testing::Header {
+		sp_runtime::testing::Header::new(
+			1,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			digest,
+		)
+	}
+
+	#[test]
+	fn authority_list_enacted_in_reads_a_scheduled_change() {
+		use codec::Encode;
+		use sp_core::crypto::UncheckedFrom;
+
+		let authorities =
+			vec![(sp_finality_grandpa::AuthorityId::unchecked_from([1u8; 32]), 1)];
+		let change =
+			sp_finality_grandpa::ScheduledChange { next_authorities: authorities.clone(), delay: 0 };
+		let digest = sp_runtime::Digest {
+			logs: vec![sp_runtime::DigestItem::Consensus(
+				sp_finality_grandpa::GRANDPA_ENGINE_ID,
+				sp_finality_grandpa::ConsensusLog::<u64>::ScheduledChange(change).encode(),
+			)],
+		};
+
+		assert_eq!(authority_list_enacted_in::<Block>(&header_with_digest(digest)), authorities);
+	}
+
+	#[test]
+	fn authority_list_enacted_in_defaults_to_empty_without_a_change_digest() {
+		let digest = sp_runtime::Digest::default();
+
+		assert_eq!(
+			authority_list_enacted_in::<Block>(&header_with_digest(digest)),
+			AuthorityList::default(),
+		);
+	}
+
+	#[test]
+	fn epoch_authorship_from_maps_the_epochs_authorship_fields() {
+		use sp_consensus_babe::AllowedSlots;
+
+		let babe_config = BabeConfiguration {
+			slot_duration: 6_000,
+			epoch_length: 200,
+			c: (1, 4),
+			authorities: vec![],
+			randomness: Default::default(),
+			allowed_slots: AllowedSlots::PrimarySlots,
+		};
+		let epoch = Epoch::genesis(&babe_config, Slot::from(42));
+
+		let authorship = epoch_authorship_from(&epoch);
+
+		assert_eq!(authorship.authorities, epoch.authorities);
+		assert_eq!(authorship.randomness, epoch.randomness);
+		assert_eq!(authorship.start_slot, epoch.start_slot);
+		assert_eq!(authorship.slot_duration, epoch.duration);
+	}
+
+	#[test]
+	fn inject_boot_nodes_dedupes_against_existing() {
+		let addr = "/ip4/127.0.0.1/tcp/30333/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+		let json = serde_json::json!({ "bootNodes": [addr] }).to_string();
+
+		let patched = inject_boot_nodes(&json, vec![boot_node(addr)]).unwrap();
+
+		let boot_nodes = serde_json::from_str::<serde_json::Value>(&patched).unwrap()["bootNodes"]
+			.as_array()
+			.unwrap()
+			.clone();
+		assert_eq!(boot_nodes, vec![serde_json::Value::String(addr.to_string())]);
+	}
+
+	#[test]
+	fn inject_boot_nodes_ignores_spec_without_boot_nodes_key() {
+		let addr = "/ip4/127.0.0.1/tcp/30333/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+		let json = serde_json::json!({ "name": "Test" }).to_string();
+
+		let patched = inject_boot_nodes(&json, vec![boot_node(addr)]).unwrap();
+
+		let value = serde_json::from_str::<serde_json::Value>(&patched).unwrap();
+		assert!(value.get("bootNodes").is_none());
+	}
+
+	#[test]
+	fn inject_boot_nodes_with_empty_extra_is_a_noop() {
+		let addr = "/ip4/127.0.0.1/tcp/30333/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+		let json = serde_json::json!({ "bootNodes": [addr] }).to_string();
+
+		let patched = inject_boot_nodes(&json, vec![]).unwrap();
+
+		let boot_nodes = serde_json::from_str::<serde_json::Value>(&patched).unwrap()["bootNodes"]
+			.as_array()
+			.unwrap()
+			.clone();
+		assert_eq!(boot_nodes, vec![serde_json::Value::String(addr.to_string())]);
 	}
 }